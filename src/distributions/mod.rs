@@ -0,0 +1,174 @@
+#![warn(missing_docs)]
+#![allow(non_snake_case)]
+
+//! Crypto Tools - Distributions
+//!
+//! This module groups samplers that draw typed values from any generator
+//! exposing the `rand_core::RngCore' interface, such as the sponge `SPRNG'.
+
+use rand_core::RngCore;
+
+/// Uniform sampler over the half-open integer range `[lo, hi)'.
+/// Bounded draws use Lemire's unbiased multiply-and-reject method, which
+/// rejects at most once in the common case and never incurs modulo bias.
+pub struct Uniform
+{
+    lo: u64,        // inclusive lower bound
+    range: u64      // hi - lo, the number of admissible values
+}
+
+impl Uniform
+{
+    /// Build a sampler over `[lo, hi)'.
+    pub fn new(lo: u64, hi: u64) -> Self {
+        assert!(hi > lo, "Uniform: empty range, expected lo < hi, got [{}, {}).", lo, hi);
+        Uniform{ lo: lo, range: hi - lo }
+    }
+
+    /// Draw a uniform value in `[lo, hi)' from `rng'.
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> u64 {
+        self.lo + bounded(rng, self.range)
+    }
+}
+
+/// Draw a uniform value in `[0, n)' from a 64-bit word using Lemire's method.
+/// The `128'-bit product `m = x * n' splits into a high half (the candidate)
+/// and a low half; when the low half falls below the rejection threshold
+/// `t = (2^64 - n) mod n' the draw is repeated to remove the bias.
+fn bounded<R: RngCore>(rng: &mut R, n: u64) -> u64 {
+    let mut m = (rng.next_u64() as u128) * (n as u128);
+    let mut lo = m as u64;
+
+    if lo < n {
+        let t = n.wrapping_neg() % n;
+        while lo < t {
+            m = (rng.next_u64() as u128) * (n as u128);
+            lo = m as u64;
+        }
+    }
+
+    (m >> 64) as u64
+}
+
+/// Draw a uniform `f64' in `[0, 1)' by filling the 53 mantissa bits.
+pub fn open01<R: RngCore>(rng: &mut R) -> f64 {
+    let bits = rng.next_u64() >> 11;
+    (bits as f64) * (1.0 / ((1_u64 << 53) as f64))
+}
+
+/// Weighted discrete sampler over indices `[0, n)' using Vose's alias method.
+/// Construction runs in `O(n)' and each draw costs `O(1)': a uniform index
+/// selects a table entry, a fair fraction then chooses between that index and
+/// its alias.
+pub struct WeightedIndex
+{
+    prob: Vec<f64>,     // acceptance probability of each index
+    alias: Vec<usize>   // fallback index when the draw is rejected
+}
+
+impl WeightedIndex
+{
+    /// Build the alias table from a slice of non-negative `weights'.
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        assert!(n > 0, "WeightedIndex: need at least one weight.");
+
+        let sum: f64 = weights.iter().sum();
+        assert!(sum > 0.0, "WeightedIndex: total weight must be positive.");
+
+        // Scale the probabilities so that their average is 1.
+        let mut scaled: Vec<f64> = weights.iter().map(|&w| {
+            assert!(w >= 0.0, "WeightedIndex: weights must be non-negative.");
+            w / sum * (n as f64)
+        }).collect();
+
+        let mut prob = vec![0.0_f64; n];
+        let mut alias = vec![0_usize; n];
+
+        // Partition indices by whether their scaled probability is below 1.
+        let mut small: Vec<usize> = Vec::new();
+        let mut large: Vec<usize> = Vec::new();
+        for (i, &p) in scaled.iter().enumerate() {
+            if p < 1.0 { small.push(i); } else { large.push(i); }
+        }
+
+        // Pair a deficient index with a surplus one, carrying the residual.
+        while !small.is_empty() && !large.is_empty() {
+            let s = small.pop().unwrap();
+            let l = large.pop().unwrap();
+
+            prob[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] = (scaled[l] + scaled[s]) - 1.0;
+
+            if scaled[l] < 1.0 { small.push(l); } else { large.push(l); }
+        }
+
+        // Flush any leftovers (only rounding error remains): accept with p = 1.
+        for l in large { prob[l] = 1.0; }
+        for s in small { prob[s] = 1.0; }
+
+        WeightedIndex{ prob: prob, alias: alias }
+    }
+
+    /// Draw a weighted index from `rng'.
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> usize {
+        let n = self.prob.len();
+        let i = Uniform::new(0, n as u64).sample(rng) as usize;
+        if open01(rng) < self.prob[i] { i } else { self.alias[i] }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::prng::sprng::SPRNG;
+
+    fn perm(x: u64) -> u64 {
+        x.rotate_left(7) ^ 0x9E37_79B9_7F4A_7C15
+    }
+
+    fn rng() -> SPRNG<u64> {
+        let bytes: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        SPRNG::<u64>::from_seed_bytes(vec![64, 32, 2, 4], perm, &bytes).unwrap()
+    }
+
+    #[test]
+    fn uniform_stays_in_range() {
+        let mut g = rng();
+        let u = Uniform::new(10, 20);
+        for _ in 0..10_000 {
+            let v = u.sample(&mut g);
+            assert!(v >= 10 && v < 20);
+        }
+    }
+
+    #[test]
+    fn open01_in_unit_interval() {
+        let mut g = rng();
+        for _ in 0..10_000 {
+            let f = open01(&mut g);
+            assert!(f >= 0.0 && f < 1.0);
+        }
+    }
+
+    #[test]
+    fn alias_table_probabilities_are_valid() {
+        // The residual/flush invariant leaves every acceptance probability in
+        // `[0, 1]' once construction completes.
+        let w = WeightedIndex::new(&[1.0, 1.0, 2.0, 4.0]);
+        for &p in &w.prob {
+            assert!(p >= 0.0 && p <= 1.0);
+        }
+        assert!(w.alias.len() == 4);
+    }
+
+    #[test]
+    fn zero_weight_is_never_drawn() {
+        let mut g = rng();
+        let w = WeightedIndex::new(&[0.0, 1.0, 0.0]);
+        for _ in 0..10_000 {
+            assert!(w.sample(&mut g) == 1);
+        }
+    }
+}
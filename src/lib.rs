@@ -5,6 +5,8 @@
 
 /// Module implementing various constructions.
 pub mod construction;
+/// Module implementing distributions that sample typed values from a PRNG.
+pub mod distributions;
 /// Module implementing Hash functions.
 pub mod hash;
 /// Module implementing Pseudo Random Number Generators.
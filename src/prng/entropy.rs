@@ -0,0 +1,174 @@
+#![warn(missing_docs)]
+#![allow(non_snake_case)]
+
+//! Crypto Tools - PRNG - Entropy
+//!
+//! Module implementing a self-contained timing-jitter entropy source, modelled
+//! on `rand`'s `JitterRng`. It harvests the non-determinism of a
+//! high-resolution monotonic clock read around deliberately noisy work and can
+//! feed the `Vec<Input>' expected by `PRNG::refresh' and the reseeding adapter
+//! on platforms lacking an OS RNG.
+
+use std::io::{Error, ErrorKind};
+use std::time::Instant;
+use std::collections::HashMap;
+use std::ops::{BitOr, Shl};
+
+/// Timing-jitter entropy collector.
+/// Each round times a memory walk over a small scratch buffer folded through a
+/// tight LFSR; the variation in those timings is the harvested entropy.
+pub struct JitterRng
+{
+    buffer: Vec<u8>,    // scratch buffer walked to create memory-latency noise
+    lfsr: u64           // running fold register carried between rounds
+}
+
+impl JitterRng
+{
+    /// Build a collector and run the startup health-check, erroring out if the
+    /// platform clock is too coarse to yield jitter.
+    pub fn new() -> Result<Self, Error> {
+        let mut rng = JitterRng{
+            buffer: vec![0_u8; 64],
+            lfsr: 0xA5A5_A5A5_A5A5_A5A5_u64
+        };
+        rng.health_check()?;
+        Ok(rng)
+    }
+
+    /// Run one noisy round and return the elapsed-time delta in nanoseconds.
+    fn round(&mut self) -> u64 {
+        let start = Instant::now();
+
+        // Memory walk plus a tight LFSR fold: both the access pattern and the
+        // register state depend on each other, defeating simple prediction.
+        let mut acc = self.lfsr;
+        let len = self.buffer.len();
+        for i in 0..len {
+            let idx = (acc as usize) % len;
+            self.buffer[idx] = self.buffer[idx].wrapping_add((i as u8) ^ (acc as u8));
+            let bit = (acc ^ (acc >> 2) ^ (acc >> 3) ^ (acc >> 5)) & 1;
+            acc = (acc >> 1) | (bit << 63);
+            acc ^= self.buffer[idx] as u64;
+        }
+        self.lfsr = acc;
+
+        start.elapsed().as_nanos() as u64
+    }
+
+    /// Gather one 64-bit block of entropy by folding the low-order noisy bits
+    /// of many per-round deltas with an XOR-and-rotate accumulator.
+    fn block(&mut self) -> u64 {
+        let mut acc = 0_u64;
+        for _ in 0..64 {
+            let delta = self.round();
+            acc = acc.rotate_left(1) ^ (delta & 0xff);
+        }
+        acc
+    }
+
+    /// Conservative floor on the harvested entropy per round, in bits.
+    const MIN_ENTROPY_BITS: f64 = 1.5;
+
+    /// Estimate the per-round entropy of a delta sequence as the Shannon
+    /// entropy (in bits) of its low-order noisy byte.
+    fn estimate_entropy(deltas: &[u64]) -> f64 {
+        let mut counts: HashMap<u64, u64> = HashMap::new();
+        for &d in deltas {
+            *counts.entry(d & 0xff).or_insert(0) += 1;
+        }
+
+        let total = deltas.len() as f64;
+        counts.values()
+            .map(|&c| { let p = c as f64 / total; -p * p.log2() })
+            .sum()
+    }
+
+    /// Accept a delta sample only if its estimated entropy clears the floor. A
+    /// clock that only ticks by a single granule (few distinct deltas) falls
+    /// below it and is rejected, where a raw variance test would wrongly pass.
+    fn check_deltas(deltas: &[u64]) -> Result<(), Error> {
+        if Self::estimate_entropy(deltas) < Self::MIN_ENTROPY_BITS {
+            return Err(Error::new(ErrorKind::Other,
+                "JitterRng: platform clock is too coarse to provide entropy."));
+        }
+        Ok(())
+    }
+
+    /// Run the startup health-check: sample the clock over many noisy rounds
+    /// and reject platforms too coarse to produce meaningful jitter.
+    fn health_check(&mut self) -> Result<(), Error> {
+        const ROUNDS: usize = 64;
+        let deltas: Vec<u64> = (0..ROUNDS).map(|_| self.round()).collect();
+        Self::check_deltas(&deltas)
+    }
+
+    /// Produce `n' freshly harvested words, suitable as inputs to
+    /// `PRNG::refresh'. Each word is filled byte-by-byte from entropy blocks.
+    pub fn gen_inputs<U>(&mut self, n: usize) -> Vec<U>
+        where U: From<u8> + BitOr<Output = U> + Shl<usize, Output = U>
+    {
+        let word_bytes = std::mem::size_of::<U>();
+        let mut out: Vec<U> = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            let mut word: U = 0_u8.into();
+            let mut filled = 0_usize;
+            while filled < word_bytes {
+                let block = self.block();
+                for k in 0..8 {
+                    if filled >= word_bytes { break; }
+                    let byte = ((block >> (8 * k)) & 0xff) as u8;
+                    word = (word << 8) | byte.into();
+                    filled += 1;
+                }
+            }
+            out.push(word);
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn check_deltas_rejects_a_coarse_clock() {
+        // Drive the real gate: a clock whose deltas are all identical carries
+        // zero entropy, and one that only jitters between two granules stays
+        // below the floor. Both must be rejected by the production path.
+        let constant = vec![42_u64; 64];
+        assert!(JitterRng::check_deltas(&constant).is_err());
+
+        let mut two_tick = vec![10_u64; 32];
+        two_tick.extend(vec![11_u64; 32]);
+        assert!(JitterRng::check_deltas(&two_tick).is_err());
+    }
+
+    #[test]
+    fn check_deltas_accepts_a_noisy_clock() {
+        // A sequence spanning many granules clears the floor and is accepted,
+        // pinning the comparison direction and threshold of the real gate.
+        let noisy: Vec<u64> = (0..64_u64).collect();
+        assert!(JitterRng::check_deltas(&noisy).is_ok());
+    }
+
+    #[test]
+    fn new_passes_health_check_on_this_platform() {
+        // Exercises the full production path, including wiring the gate into
+        // `new', on a real (jittery) monotonic clock.
+        assert!(JitterRng::new().is_ok());
+    }
+
+    #[test]
+    fn gen_inputs_returns_requested_count() {
+        let mut rng = JitterRng{
+            buffer: vec![0_u8; 64],
+            lfsr: 0xA5A5_A5A5_A5A5_A5A5_u64
+        };
+        let words: Vec<u64> = rng.gen_inputs(5);
+        assert!(words.len() == 5);
+    }
+}
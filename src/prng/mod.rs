@@ -27,4 +27,8 @@ pub trait PRNG
 }
 
 /// Module implementing the Sponge-based PRNG of Gazi and Tessaro [GT2016].
-pub mod sprng;
\ No newline at end of file
+pub mod sprng;
+/// Module implementing a reseeding adapter for automatic periodic refresh.
+pub mod reseeding;
+/// Module implementing a timing-jitter entropy source for seeding/reseeding.
+pub mod entropy;
\ No newline at end of file
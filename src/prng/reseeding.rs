@@ -0,0 +1,140 @@
+#![warn(missing_docs)]
+#![allow(non_snake_case)]
+
+//! Crypto Tools - PRNG - Reseeding
+//!
+//! Module implementing a reseeding adapter that wraps any [`PRNG`] together
+//! with an entropy source and automatically re-injects fresh inputs after a
+//! configurable amount of output, analogous to `rand::rngs::adapter::ReseedingRng`.
+
+use std::io::Error;
+use crate::prng::PRNG;
+
+/// Adapter wrapping a [`PRNG`] with an entropy source for periodic refresh.
+/// The `source' yields a fresh `Vec' of inputs; after `threshold' bytes of
+/// output have been produced, it is polled and fed to the inner `refresh',
+/// giving forward-security / recovery semantics on top of the sponge.
+pub struct ReseedingPRNG<P, S>
+    where P: PRNG, S: FnMut() -> Vec<P::Input>
+{
+    inner: P,                   // wrapped generator
+    source: S,                  // entropy source yielding refresh inputs
+    threshold: usize,           // output bytes between two reseeds
+    bytes_since_reseed: usize   // output bytes produced since the last reseed
+}
+
+impl<P, S> ReseedingPRNG<P, S>
+    where P: PRNG, S: FnMut() -> Vec<P::Input>
+{
+    /// Wrap `inner' so that it is reseeded from `source' every `threshold'
+    /// bytes of output.
+    pub fn new(inner: P, source: S, threshold: usize) -> Self {
+        ReseedingPRNG{
+            inner: inner,
+            source: source,
+            threshold: threshold,
+            bytes_since_reseed: 0_usize
+        }
+    }
+
+    /// Poll the source and reseed the inner generator immediately, resetting
+    /// the output counter.
+    pub fn reseed(&mut self) -> Result<(), Error> {
+        let inputs = (self.source)();
+        self.inner.refresh(inputs)?;
+        self.bytes_since_reseed = 0_usize;
+        Ok(())
+    }
+}
+
+impl<P, S> PRNG for ReseedingPRNG<P, S>
+    where P: PRNG, S: FnMut() -> Vec<P::Input>
+{
+    type Input = P::Input;
+    type Output = P::Output;
+
+    /// Refresh the inner generator with the caller's inputs, which also
+    /// resets the reseed counter.
+    fn refresh(&mut self, inputs: Vec<Self::Input>) -> Result<(), Error> {
+        self.inner.refresh(inputs)?;
+        self.bytes_since_reseed = 0_usize;
+        Ok(())
+    }
+
+    /// Reseed from the source first when the threshold has been reached, then
+    /// squeeze the inner generator and account for the produced output width.
+    fn next(&mut self) -> Result<Self::Output, Error> {
+        if self.bytes_since_reseed >= self.threshold {
+            self.reseed()?;
+        }
+
+        let output = self.inner.next()?;
+        self.bytes_since_reseed += std::mem::size_of::<Self::Output>();
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    /// Minimal counting generator: `next' yields 4-byte outputs and `refresh'
+    /// records how many times it has been re-seeded.
+    struct Counter {
+        value: u32,
+        refreshes: Rc<RefCell<usize>>
+    }
+
+    impl PRNG for Counter {
+        type Input = u32;
+        type Output = u32;
+
+        fn refresh(&mut self, _inputs: Vec<u32>) -> Result<(), Error> {
+            *self.refreshes.borrow_mut() += 1;
+            Ok(())
+        }
+
+        fn next(&mut self) -> Result<u32, Error> {
+            self.value = self.value.wrapping_add(1);
+            Ok(self.value)
+        }
+    }
+
+    #[test]
+    fn reseeds_once_threshold_bytes_produced() {
+        let refreshes = Rc::new(RefCell::new(0_usize));
+        let inner = Counter{ value: 0, refreshes: refreshes.clone() };
+
+        // Output is `u32' (4 bytes); a 16-byte threshold is four draws.
+        let mut rng = ReseedingPRNG::new(inner, || vec![0_u32], 16);
+
+        // The first four draws stay under the threshold: no reseed yet.
+        for _ in 0..4 { rng.next().unwrap(); }
+        assert!(*refreshes.borrow() == 0);
+
+        // The fifth draw sees the counter at 16 and reseeds before squeezing.
+        rng.next().unwrap();
+        assert!(*refreshes.borrow() == 1);
+    }
+
+    #[test]
+    fn reseed_resets_the_counter() {
+        let refreshes = Rc::new(RefCell::new(0_usize));
+        let inner = Counter{ value: 0, refreshes: refreshes.clone() };
+        let mut rng = ReseedingPRNG::new(inner, || vec![0_u32], 16);
+
+        // Three draws (12 bytes), then an on-demand reseed clears the counter.
+        for _ in 0..3 { rng.next().unwrap(); }
+        rng.reseed().unwrap();
+        assert!(*refreshes.borrow() == 1);
+        assert!(rng.bytes_since_reseed == 0);
+
+        // After the reset it again takes a full threshold to fire automatically.
+        for _ in 0..4 { rng.next().unwrap(); }
+        assert!(*refreshes.borrow() == 1);
+        rng.next().unwrap();
+        assert!(*refreshes.borrow() == 2);
+    }
+}
@@ -6,8 +6,10 @@
 //! Module implementing the sponge-based PRNG of Gazi and Tessaro [GT2016].
 
 use std::io::Error;
+use std::convert::TryInto;
 use rand::{Rng, thread_rng, distributions::Standard, prelude::Distribution};
-use std::{ops::{BitXor, BitAnd, BitOr, Not, Sub, Shl}, convert::From};
+use rand_core::{RngCore, SeedableRng, Error as RandError};
+use std::{ops::{BitXor, BitAnd, BitOr, Not, Sub, Shl, Shr}, convert::From};
 use crate::prng::PRNG;
 
 #[derive(Clone, Debug)]
@@ -17,12 +19,15 @@ use crate::prng::PRNG;
 pub struct SPRNG<U>
 {
     t: usize,           // number of permutation rounds in `next'
+    r: usize,           // rate (number of outer bits)
     s: usize,           // length of the seed vector'
     j: usize,           // seed iterator
     mask: U,            // mask used to extract the outer part
     perm: fn(U) -> U,   // permutation function
     seed: Vec<U>,       // seed vector
-    state: U            // state of the sponge
+    state: U,           // state of the sponge
+    bit_acc: u128,      // leftover output bits between `fill_bytes' calls
+    bit_cnt: usize      // number of valid low bits held in `bit_acc'
 }
 
 impl<U> SPRNG<U>
@@ -30,35 +35,72 @@ impl<U> SPRNG<U>
         Not<Output = U> + BitOr<Output = U> + Sub<Output = U>, Standard: Distribution<U>
 {
     /// Setup function.
+    /// Draws the entropy once from `thread_rng' and routes it through the
+    /// deterministic `from_seed_bytes' constructor, so that random and
+    /// reproducible setups share the exact same state derivation.
     pub fn new(params: Vec<usize>, func: fn(U) -> U) -> Result<Self, Error> {
+        assert!(params.len() == 4, "SPRNG Setup: wrong number of parameters for setup. Expected 4, got {}.", params.len());
+        let s = params[3];
+
+        // Sample enough bytes to fill the s seed words and the initial state.
+        let word_bytes = std::mem::size_of::<U>();
+        let mut rng = thread_rng();
+        let seed: Vec<u8> = (0..(s + 1) * word_bytes).map(|_| rng.gen::<u8>()).collect();
+
+        Self::from_seed_bytes(params, func, &seed)
+    }
+
+    /// Deterministic setup function.
+    /// Derives the `s'-entry seed vector and the inner state from `seed' by
+    /// chunking the byte slice into `U'-sized words (cycling the slice if it is
+    /// short) and masking each word appropriately, separating entropy
+    /// acquisition from state initialisation as `SeedableRng::from_seed' does.
+    pub fn from_seed_bytes(params: Vec<usize>, func: fn(U) -> U, seed: &[u8]) -> Result<Self, Error> {
         assert!(params.len() == 4, "SPRNG Setup: wrong number of parameters for setup. Expected 4, got {}.", params.len());
         let (n, r, t, s) = (params[0], params[1], params[2], params[3]);
         assert!(r <= n, "SPRNG Setup: rate r must be less than or equal to the state size n.");
+        assert!(r > 0, "SPRNG Setup: rate r must be strictly positive.");
+        // `fill_bytes' buffers leftover bits in a 128-bit accumulator that holds
+        // up to `r + 7' bits at once, so the rate must fit that width.
+        assert!(r + 7 <= 128, "SPRNG Setup: rate r must be at most 121 to fit the output bit buffer.");
         assert!(s > 1, "SPRNG Setup: seed size s must be greater than 1.");
+        assert!(!seed.is_empty(), "SPRNG Setup: seed bytes must not be empty.");
 
         // Generate the mask
         let mut mask: U = 1_u8.into();
         mask = (mask << r) - 1_u8.into();
 
-        // Generate the seed using rand
-        let mut rng = thread_rng();
+        // Fold `size_of::<U>()' bytes of the slice into one word, cycling the
+        // slice so that short seeds still fully populate every word.
+        let word_bytes = std::mem::size_of::<U>();
+        let mut cursor = 0_usize;
+        let mut next_word = || {
+            let mut word: U = 0_u8.into();
+            for _ in 0..word_bytes {
+                word = (word << 8) | seed[cursor % seed.len()].into();
+                cursor += 1;
+            }
+            word
+        };
+
+        // The seed vector holds outer-part words, the state an inner-part word.
         let mut seed_vec: Vec<U> = Vec::with_capacity(s);
         for _ in 0..s {
-            seed_vec.push(rng.gen::<U>() & mask);
+            seed_vec.push(next_word() & mask);
         }
-
-        // Initial state is r '0' bits and c random bits (n=c+r)
-        let mut state: U = 0_u8.into();
-        state = state | (rng.gen::<U>() & !mask);
+        let state: U = next_word() & !mask;
 
         Ok(SPRNG{
             t: t,
+            r: r,
             s: s,
             j: 1_usize,
             mask: mask,
             perm: func,
             seed: seed_vec,
-            state: state
+            state: state,
+            bit_acc: 0_u128,
+            bit_cnt: 0_usize
         })
     }
 
@@ -120,4 +162,141 @@ impl<U> PRNG for SPRNG<U>
 
         Ok(R)
     }
+}
+
+// Bridge the sponge into the wider `rand' ecosystem. `fill_bytes' repeatedly
+// squeezes the sponge, keeps the low `r' bits of each output (via `self.mask',
+// already applied by `next') and packs those bits into the buffer, carrying any
+// leftover bits in `self.bit_acc'/`self.bit_cnt' between calls so that a rate
+// that is not a multiple of 8 still yields an unbiased byte stream. `next_u32'
+// and `next_u64' are built on top of `fill_bytes'.
+impl<U> RngCore for SPRNG<U>
+    where U: Copy + From<u8> + Not<Output = U> + BitAnd<Output = U> + BitXor<Output = U> +
+        Shr<usize, Output = U> + TryInto<u8>, <U as TryInto<u8>>::Error: std::fmt::Debug
+{
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0_u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0_u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // Number of bytes needed to carry the `r' significant bits of a squeeze.
+        let nbytes = (self.r + 7) / 8;
+
+        for slot in dest.iter_mut() {
+            // Squeeze fresh blocks until a full byte of output is buffered,
+            // appending each block's low `r' bits above the leftover bits.
+            while self.bit_cnt < 8 {
+                let word = self.next().expect("SPRNG: sponge squeeze failed");
+                let mut bits: u128 = 0;
+                for k in 0..nbytes {
+                    let byte: u8 = ((word >> (8 * k)) & 0xFF_u8.into())
+                        .try_into()
+                        .expect("SPRNG: byte extraction failed");
+                    bits |= (byte as u128) << (8 * k);
+                }
+                // Discard padding above bit `r', keeping only the entropy.
+                // `from_seed_bytes' asserts `r + 7 <= 128', so the shift is safe.
+                bits &= (1_u128 << self.r) - 1;
+                self.bit_acc |= bits << self.bit_cnt;
+                self.bit_cnt += self.r;
+            }
+
+            *slot = (self.bit_acc & 0xFF) as u8;
+            self.bit_acc >>= 8;
+            self.bit_cnt -= 8;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Default sponge permutation used when a construction cannot supply one (as
+/// when seeding through `SeedableRng::from_seed'). It mixes the state with a
+/// single rotate-xor round: enough to make the generator a usable drop-in
+/// `rand' RNG, while callers wanting the full Gazì–Tessaro security build the
+/// sponge through `from_seed_bytes' with their own permutation.
+fn default_perm<U>(x: U) -> U
+    where U: Copy + From<u8> + Shl<usize, Output = U> + Shr<usize, Output = U> +
+        BitOr<Output = U> + BitXor<Output = U>
+{
+    let bits = std::mem::size_of::<U>() * 8;
+    ((x << 7) | (x >> (bits - 7))) ^ 0x9E_u8.into()
+}
+
+// Seeding from a bare byte array. The sponge permutation is a construction
+// parameter a seed cannot carry, so `from_seed' pins the default permutation
+// and a canonical parameter set derived from the word width, then routes the
+// seed bytes through the deterministic `from_seed_bytes' constructor.
+impl<U> SeedableRng for SPRNG<U>
+    where U: Copy + Clone + From<u8> + Shl<usize, Output = U> + Shr<usize, Output = U> +
+        BitAnd<Output = U> + Not<Output = U> + BitOr<Output = U> + BitXor<Output = U> +
+        Sub<Output = U>, Standard: Distribution<U>
+{
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let n = std::mem::size_of::<U>() * 8;
+        // Rate half the state, four permutation rounds per output, a four-word
+        // seed vector.
+        let params = vec![n, n / 2, 4, 4];
+        Self::from_seed_bytes(params, default_perm::<U>, &seed)
+            .expect("SPRNG: default seeding parameters are always valid")
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn perm(x: u64) -> u64 {
+        x.rotate_left(7) ^ 0x9E37_79B9_7F4A_7C15
+    }
+
+    #[test]
+    fn from_seed_bytes_is_reproducible() {
+        let params = vec![64, 32, 2, 4];
+        let bytes: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+
+        let a = SPRNG::<u64>::from_seed_bytes(params.clone(), perm, &bytes).unwrap();
+        let b = SPRNG::<u64>::from_seed_bytes(params, perm, &bytes).unwrap();
+
+        // Same bytes and parameters must derive exactly the same state.
+        assert!(a.get_seed() == b.get_seed());
+        assert!(a.get_mask() == b.get_mask());
+    }
+
+    #[test]
+    fn identical_seeds_yield_identical_streams() {
+        let params = vec![64, 32, 2, 4];
+        let bytes: [u8; 16] = [17, 4, 99, 8, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+
+        let mut a = SPRNG::<u64>::from_seed_bytes(params.clone(), perm, &bytes).unwrap();
+        let mut b = SPRNG::<u64>::from_seed_bytes(params, perm, &bytes).unwrap();
+
+        for _ in 0..8 {
+            assert!(a.next().unwrap() == b.next().unwrap());
+        }
+    }
+
+    #[test]
+    fn new_routes_through_deterministic_masking() {
+        // `new' samples entropy then routes it through `from_seed_bytes', so the
+        // seed vector must carry the same outer-part-only invariant.
+        let sprng = SPRNG::<u64>::new(vec![64, 32, 2, 4], perm).unwrap();
+        let mask = sprng.get_mask();
+        for word in sprng.get_seed() {
+            assert!(word & !mask == 0);
+        }
+    }
 }
\ No newline at end of file